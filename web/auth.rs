@@ -0,0 +1,182 @@
+use crate::types::{ApiResponse, ErrorCode, ErrorResponse};
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage, HttpResponse,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::rc::Rc;
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Claims carried by a validated token.
+#[derive(Debug, Clone)]
+pub struct Claims {
+    pub expiry: u64,
+    pub caller_id: Option<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("missing bearer token")]
+    Missing,
+    #[error("malformed token")]
+    Malformed,
+    #[error("invalid token signature")]
+    BadSignature,
+    #[error("token has expired")]
+    Expired,
+}
+
+/// Mint a token: an HMAC-SHA256 signature over `"<expiry>:<caller>"`, with the
+/// message and MAC each base64url-encoded and joined by a `.`.
+pub fn mint(secret: &str, expiry_unix: u64, caller_id: Option<&str>) -> String {
+    let message = format!("{}:{}", expiry_unix, caller_id.unwrap_or(""));
+    let mac = sign(secret, message.as_bytes());
+    format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(message.as_bytes()),
+        URL_SAFE_NO_PAD.encode(mac)
+    )
+}
+
+/// Validate a token by recomputing the MAC and checking the expiry against
+/// `now_unix`.
+pub fn verify(secret: &str, token: &str, now_unix: u64) -> Result<Claims, AuthError> {
+    let (message_b64, mac_b64) = token.split_once('.').ok_or(AuthError::Malformed)?;
+    let message = URL_SAFE_NO_PAD
+        .decode(message_b64)
+        .map_err(|_| AuthError::Malformed)?;
+    let provided_mac = URL_SAFE_NO_PAD
+        .decode(mac_b64)
+        .map_err(|_| AuthError::Malformed)?;
+
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(&message);
+    mac.verify_slice(&provided_mac)
+        .map_err(|_| AuthError::BadSignature)?;
+
+    let message = String::from_utf8(message).map_err(|_| AuthError::Malformed)?;
+    let (expiry_str, caller) = message.split_once(':').ok_or(AuthError::Malformed)?;
+    let expiry: u64 = expiry_str.parse().map_err(|_| AuthError::Malformed)?;
+    if now_unix >= expiry {
+        return Err(AuthError::Expired);
+    }
+
+    Ok(Claims {
+        expiry,
+        caller_id: (!caller.is_empty()).then(|| caller.to_string()),
+    })
+}
+
+fn sign(secret: &str, message: &[u8]) -> Vec<u8> {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Actix middleware enforcing bearer-token authentication. When constructed with
+/// an empty secret it is a transparent pass-through, so the endpoints stay open
+/// until an operator configures `auth.secret`.
+#[derive(Clone)]
+pub struct HmacAuth {
+    secret: Option<Rc<String>>,
+}
+
+impl HmacAuth {
+    pub fn new(secret: &str) -> Self {
+        Self {
+            secret: (!secret.is_empty()).then(|| Rc::new(secret.to_string())),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for HmacAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = HmacAuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(HmacAuthMiddleware {
+            service: Rc::new(service),
+            secret: self.secret.clone(),
+        }))
+    }
+}
+
+pub struct HmacAuthMiddleware<S> {
+    service: Rc<S>,
+    secret: Option<Rc<String>>,
+}
+
+impl<S, B> Service<ServiceRequest> for HmacAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let secret = self.secret.clone();
+
+        Box::pin(async move {
+            if let Some(secret) = secret.as_deref() {
+                match authenticate(&req, secret) {
+                    Ok(claims) => {
+                        req.extensions_mut().insert(claims);
+                    }
+                    Err(error) => {
+                        let response = HttpResponse::Unauthorized()
+                            .json(unauthorized(&error))
+                            .map_into_right_body();
+                        let (request, _payload) = req.into_parts();
+                        return Ok(ServiceResponse::new(request, response));
+                    }
+                }
+            }
+            service.call(req).await.map(|res| res.map_into_left_body())
+        })
+    }
+}
+
+fn authenticate(req: &ServiceRequest, secret: &str) -> Result<Claims, AuthError> {
+    let token = req
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(AuthError::Missing)?;
+    verify(secret, token.trim(), unix_now())
+}
+
+fn unauthorized(error: &AuthError) -> ApiResponse {
+    ApiResponse::Error(ErrorResponse {
+        code: ErrorCode::Unauthorized,
+        message: "Unauthorized".to_string(),
+        details: Some(error.to_string()),
+    })
+}
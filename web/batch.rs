@@ -0,0 +1,135 @@
+use crate::types::ApiResponse;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// In-flight and recently finished batch jobs, keyed by job id. Large batches
+/// are processed in the background and their progress polled via
+/// `/validate-batch/{id}`; entries are evicted on the same hourly schedule as
+/// [`RESULT_CACHE`](crate::result_cache::RESULT_CACHE).
+lazy_static! {
+    pub(crate) static ref JOB_CACHE: DashMap<String, JobState> = DashMap::new();
+}
+
+const JOB_TTL: Duration = Duration::from_secs(3600);
+
+static JOB_COUNTER: AtomicU64 = AtomicU64::new(1);
+
+/// Lifecycle of a batch job, surfaced to pollers as the job progresses.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+}
+
+/// Mutable server-side state for a background batch job. Per-item outcomes are
+/// slotted in by input index so a snapshot preserves request order regardless of
+/// the order items actually finish.
+pub struct JobState {
+    status: JobStatus,
+    total: usize,
+    completed: usize,
+    failed: usize,
+    results: Vec<Option<ApiResponse>>,
+    created: Instant,
+    /// Caller id from the token that created the job, when auth is enabled.
+    /// `None` (auth disabled, or an unscoped token) leaves the job visible to
+    /// any caller, matching the endpoint's behaviour before auth existed.
+    caller_id: Option<String>,
+}
+
+/// Snapshot of a job's progress returned from the batch endpoints. For inline
+/// (synchronous) batches `job_id` is omitted because there is nothing to poll.
+#[derive(Debug, Serialize)]
+pub struct BatchStatusResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    pub status: JobStatus,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub results: Vec<ApiResponse>,
+}
+
+/// Acknowledgement returned immediately for batches processed in the background.
+#[derive(Debug, Serialize)]
+pub struct BatchAccepted {
+    pub job_id: String,
+    pub status: JobStatus,
+    pub total: usize,
+}
+
+/// Register a new background job of `total` items, owned by `caller_id` when
+/// auth is enabled, and return its id.
+pub fn create_job(total: usize, caller_id: Option<String>) -> String {
+    let id = format!("{:016x}", JOB_COUNTER.fetch_add(1, Ordering::Relaxed));
+    JOB_CACHE.insert(
+        id.clone(),
+        JobState {
+            status: JobStatus::Running,
+            total,
+            completed: 0,
+            failed: 0,
+            results: (0..total).map(|_| None).collect(),
+            created: Instant::now(),
+            caller_id,
+        },
+    );
+    id
+}
+
+/// Record the outcome of item `index` and advance the completed/failed counters.
+pub fn record(id: &str, index: usize, outcome: ApiResponse) {
+    if let Some(mut job) = JOB_CACHE.get_mut(id) {
+        if matches!(outcome, ApiResponse::Error(_)) {
+            job.failed += 1;
+        }
+        job.completed += 1;
+        if let Some(slot) = job.results.get_mut(index) {
+            *slot = Some(outcome);
+        }
+    }
+}
+
+/// Mark a job finished once every item has been recorded.
+pub fn complete_job(id: &str) {
+    if let Some(mut job) = JOB_CACHE.get_mut(id) {
+        job.status = JobStatus::Completed;
+    }
+}
+
+/// Build a progress snapshot for `id`, or `None` if the job is unknown,
+/// expired, or (when the job is owned by a caller) `caller_id` doesn't match
+/// the caller that created it. The unknown and forbidden cases are
+/// deliberately indistinguishable so a caller can't use this endpoint to
+/// probe for other callers' job ids.
+pub fn snapshot(id: &str, caller_id: Option<&str>) -> Option<BatchStatusResponse> {
+    let job = JOB_CACHE.get(id)?;
+    if job.created.elapsed() >= JOB_TTL {
+        return None;
+    }
+    if let Some(owner) = job.caller_id.as_deref() {
+        if caller_id != Some(owner) {
+            return None;
+        }
+    }
+    Some(BatchStatusResponse {
+        job_id: Some(id.to_string()),
+        status: job.status,
+        total: job.total,
+        completed: job.completed,
+        failed: job.failed,
+        results: job.results.iter().flatten().cloned().collect(),
+    })
+}
+
+pub fn cleanup_expired_jobs() {
+    JOB_CACHE.retain(|_, job| job.created.elapsed() < JOB_TTL);
+}
+
+pub fn cleanup_job_cache() {
+    JOB_CACHE.clear();
+}
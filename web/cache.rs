@@ -1,6 +1,7 @@
+use crate::config::ModelConfig;
 use dashmap::DashMap;
 use lazy_static::lazy_static;
-use ort::{Environment, Session};
+use ort::{Environment, GraphOptimizationLevel, Session};
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -27,22 +28,30 @@ pub fn cleanup_expired_sessions() {
 
 pub fn get_or_create_session(
     environment: &Arc<Environment>,
-    model_path: &str,
+    model: &ModelConfig,
 ) -> Result<Arc<Session>, Box<dyn std::error::Error>> {
     const CACHE_TIMEOUT: Duration = Duration::from_secs(3600);
 
+    let model_path = model.path.as_str();
+
     if let Some(cached) = SESSION_CACHE.get(model_path) {
         if cached.last_used.elapsed() < CACHE_TIMEOUT {
+            tracing::debug!(model = model_path, cache = "hit", "reusing cached session");
             return Ok(cached.session.clone());
         }
         SESSION_CACHE.remove(model_path);
     }
 
+    tracing::debug!(model = model_path, cache = "miss", "building new session");
+
+    let intra_threads = model.intra_threads.unwrap_or_else(num_cpus::get).max(1);
+    let inter_threads = model.inter_threads.unwrap_or_else(num_cpus::get).max(1);
+
     let new_session = Arc::new(
         ort::SessionBuilder::new(environment)?
-            .with_optimization_level(ort::GraphOptimizationLevel::Level3)?
-            .with_intra_threads(i16::try_from(num_cpus::get().max(1)).unwrap_or(1))?
-            .with_inter_threads(i16::try_from(num_cpus::get().max(1)).unwrap_or(1))?
+            .with_optimization_level(optimization_level(model.optimization_level))?
+            .with_intra_threads(i16::try_from(intra_threads).unwrap_or(1))?
+            .with_inter_threads(i16::try_from(inter_threads).unwrap_or(1))?
             .with_memory_pattern(true)?
             .with_model_from_file(model_path)?,
     );
@@ -57,3 +66,12 @@ pub fn get_or_create_session(
 
     Ok(new_session)
 }
+
+fn optimization_level(level: u8) -> GraphOptimizationLevel {
+    match level {
+        0 => GraphOptimizationLevel::Disable,
+        1 => GraphOptimizationLevel::Level1,
+        2 => GraphOptimizationLevel::Level2,
+        _ => GraphOptimizationLevel::Level3,
+    }
+}
@@ -1,8 +1,9 @@
-use crate::types::{Detection, DEFAULT_CLUSTER_SIZE, IOU_THRESHOLD};
+use crate::config::DetectionConfig;
+use crate::types::Detection;
 use dbscan::cluster;
 use rayon::prelude::*;
 
-pub fn cluster_boxes(detections: &mut Vec<Detection>) -> Vec<[i32; 4]> {
+pub fn cluster_boxes(detections: &mut Vec<Detection>, config: &DetectionConfig) -> Vec<[i32; 4]> {
     if detections.is_empty() {
         return Vec::new();
     }
@@ -17,7 +18,7 @@ pub fn cluster_boxes(detections: &mut Vec<Detection>) -> Vec<[i32; 4]> {
         ]
     }));
 
-    let eps = calculate_median_size(detections).max(DEFAULT_CLUSTER_SIZE) * 0.5;
+    let eps = calculate_median_size(detections, config.cluster_size).max(config.cluster_size) * 0.5;
     let min_points = if detections.len() > 3 { 2 } else { 1 };
 
     let clusters = cluster(eps, min_points, &points);
@@ -25,12 +26,12 @@ pub fn cluster_boxes(detections: &mut Vec<Detection>) -> Vec<[i32; 4]> {
         .iter()
         .all(|c| matches!(c, dbscan::Classification::Noise))
     {
-        return process_clusters(detections, clusters);
+        return process_clusters(detections, clusters, config);
     }
-    process_clusters(detections, cluster(eps * 1.5, min_points, &points))
+    process_clusters(detections, cluster(eps * 1.5, min_points, &points), config)
 }
 
-fn calculate_median_size(detections: &[Detection]) -> f64 {
+fn calculate_median_size(detections: &[Detection], default_cluster_size: f64) -> f64 {
     let mut sizes: Vec<f64> = detections
         .iter()
         .map(|det| {
@@ -44,12 +45,13 @@ fn calculate_median_size(detections: &[Detection]) -> f64 {
     sizes
         .get(sizes.len() / 2)
         .copied()
-        .unwrap_or(DEFAULT_CLUSTER_SIZE)
+        .unwrap_or(default_cluster_size)
 }
 
 fn process_clusters(
     detections: &[Detection],
     clusters: Vec<dbscan::Classification>,
+    config: &DetectionConfig,
 ) -> Vec<[i32; 4]> {
     let mut final_boxes = Vec::new();
     let mut cluster_map: std::collections::HashMap<usize, Vec<[i32; 4]>> =
@@ -68,7 +70,12 @@ fn process_clusters(
                     .push(detections[idx].bbox);
             }
             dbscan::Classification::Noise => {
-                handle_noise_point(&mut final_boxes, &mut cluster_map, detections[idx].bbox);
+                handle_noise_point(
+                    &mut final_boxes,
+                    &mut cluster_map,
+                    detections[idx].bbox,
+                    config.iou_threshold,
+                );
             }
         }
     }
@@ -80,12 +87,13 @@ fn handle_noise_point(
     final_boxes: &mut Vec<[i32; 4]>,
     cluster_map: &mut std::collections::HashMap<usize, Vec<[i32; 4]>>,
     bbox: [i32; 4],
+    iou_threshold: f32,
 ) {
     let mut merged = false;
     for boxes in cluster_map.values_mut() {
         if boxes.iter().any(|existing_box| {
             let iou = calculate_iou(&bbox, existing_box);
-            iou.is_finite() && iou > IOU_THRESHOLD
+            iou.is_finite() && iou > iou_threshold
         }) {
             boxes.push(bbox);
             merged = true;
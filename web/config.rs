@@ -0,0 +1,331 @@
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Default model shipped with the service.
+const DEFAULT_MODEL_PATH: &str = "models/yolo11n_9ir_640_hface.onnx";
+
+/// Top-level runtime configuration, populated from a TOML file and used to build
+/// [`AppState`](crate::types::AppState). Every section falls back to defaults that
+/// match the previously hardcoded constants when keys (or the file) are absent.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub media: MediaConfig,
+    pub model: ModelConfig,
+    pub detection: DetectionConfig,
+    pub batch: BatchConfig,
+    pub recognition: RecognitionConfig,
+    pub telemetry: TelemetryConfig,
+    pub auth: AuthConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub address: String,
+    pub keep_alive_secs: u64,
+    pub request_timeout_secs: u64,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:8080".to_string(),
+            keep_alive_secs: 30,
+            request_timeout_secs: 60,
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn keep_alive(&self) -> Duration {
+        Duration::from_secs(self.keep_alive_secs)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MediaConfig {
+    pub max_upload_bytes: usize,
+    pub allowed_mime_types: Vec<String>,
+    pub max_width: u32,
+    pub max_height: u32,
+    pub max_area: u64,
+}
+
+impl Default for MediaConfig {
+    fn default() -> Self {
+        Self {
+            max_upload_bytes: 10 * 1024 * 1024,
+            allowed_mime_types: vec![
+                "image/jpeg".to_string(),
+                "image/png".to_string(),
+                "image/webp".to_string(),
+                "application/json".to_string(),
+            ],
+            max_width: 10_000,
+            max_height: 10_000,
+            max_area: 40_000_000,
+        }
+    }
+}
+
+impl MediaConfig {
+    pub fn is_allowed(&self, mime: &str) -> bool {
+        self.allowed_mime_types.iter().any(|m| m == mime)
+    }
+
+    /// Validate image dimensions (as read from the header, before a full decode)
+    /// against the configured ceilings. A limit
+    /// of `0` disables that particular check. Returns a human-readable reason when
+    /// the image exceeds any limit, guarding against decompression-bomb inputs.
+    pub fn check_dimensions(&self, width: u32, height: u32) -> Result<(), String> {
+        if self.max_width != 0 && width > self.max_width {
+            return Err(format!(
+                "width {} exceeds maximum of {}",
+                width, self.max_width
+            ));
+        }
+        if self.max_height != 0 && height > self.max_height {
+            return Err(format!(
+                "height {} exceeds maximum of {}",
+                height, self.max_height
+            ));
+        }
+        let area = u64::from(width) * u64::from(height);
+        if self.max_area != 0 && area > self.max_area {
+            return Err(format!(
+                "area {} exceeds maximum of {} pixels",
+                area, self.max_area
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelConfig {
+    pub path: String,
+    pub optimization_level: u8,
+    /// Intra-op thread count; `None` defaults to the number of logical CPUs.
+    pub intra_threads: Option<usize>,
+    /// Inter-op thread count; `None` defaults to the number of logical CPUs.
+    pub inter_threads: Option<usize>,
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        Self {
+            path: DEFAULT_MODEL_PATH.to_string(),
+            optimization_level: 3,
+            intra_threads: None,
+            inter_threads: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DetectionConfig {
+    pub conf_threshold: f32,
+    pub iou_threshold: f32,
+    pub input_width: u32,
+    pub input_height: u32,
+    pub cluster_size: f64,
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        Self {
+            conf_threshold: 0.6,
+            iou_threshold: 0.45,
+            input_width: 640,
+            input_height: 640,
+            cluster_size: 50.0,
+        }
+    }
+}
+
+impl DetectionConfig {
+    pub fn input_size(&self) -> (u32, u32) {
+        (self.input_width, self.input_height)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatchConfig {
+    /// Maximum number of images validated concurrently; `None` defaults to the
+    /// number of logical CPUs.
+    pub max_concurrency: Option<usize>,
+    /// Batches with at most this many images are processed synchronously; larger
+    /// batches return a job id for progress polling.
+    pub inline_threshold: usize,
+    /// Hard ceiling on the number of images accepted in a single batch request.
+    pub max_items: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: None,
+            inline_threshold: 8,
+            max_items: 256,
+        }
+    }
+}
+
+impl BatchConfig {
+    pub fn concurrency(&self) -> usize {
+        self.max_concurrency.unwrap_or_else(num_cpus::get).max(1)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecognitionConfig {
+    /// Path to the ONNX recognition model. An empty path disables embedding
+    /// extraction and the `/compare-faces` endpoint.
+    pub path: String,
+    pub input_width: u32,
+    pub input_height: u32,
+    /// Cosine-similarity threshold at or above which two faces are a match.
+    pub match_threshold: f32,
+}
+
+impl Default for RecognitionConfig {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            input_width: 112,
+            input_height: 112,
+            match_threshold: 0.5,
+        }
+    }
+}
+
+impl RecognitionConfig {
+    pub fn configured(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    pub fn input_size(&self) -> (u32, u32) {
+        (self.input_width, self.input_height)
+    }
+
+    /// Build a [`ModelConfig`] for the recognition model so it reuses the shared
+    /// [`SESSION_CACHE`](crate::cache::SESSION_CACHE) and the detector's runtime
+    /// tuning, keyed by its own path.
+    pub fn model_config(&self, base: &ModelConfig) -> ModelConfig {
+        ModelConfig {
+            path: self.path.clone(),
+            ..base.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TelemetryConfig {
+    /// Export spans to an OTLP collector in addition to the stderr formatter.
+    /// Can also be toggled on with the `FACE_VALIDATION_OTLP` environment variable.
+    pub otlp_enabled: bool,
+    /// OTLP/gRPC endpoint the collector listens on.
+    pub otlp_endpoint: String,
+    /// Service name reported alongside exported spans.
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_enabled: false,
+            otlp_endpoint: "http://127.0.0.1:4317".to_string(),
+            service_name: "face-validation".to_string(),
+        }
+    }
+}
+
+impl TelemetryConfig {
+    /// OTLP export is on when the config flag is set or the environment toggle is
+    /// truthy, so deployments can enable it without editing the config file.
+    pub fn otlp_enabled(&self) -> bool {
+        self.otlp_enabled
+            || std::env::var("FACE_VALIDATION_OTLP")
+                .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Shared secret used to sign and verify bearer tokens. When empty the
+    /// validation endpoints are left unauthenticated.
+    pub secret: String,
+    /// Default token lifetime, in seconds, used by the `mint-token` helper.
+    pub token_ttl_secs: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            secret: String::new(),
+            token_ttl_secs: 3600,
+        }
+    }
+}
+
+impl AuthConfig {
+    pub fn enabled(&self) -> bool {
+        !self.secret.is_empty()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file {path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("Failed to parse config file {path}: {source}")]
+    Parse {
+        path: String,
+        source: toml::de::Error,
+    },
+}
+
+impl Config {
+    /// Resolve and load the configuration. The path is taken from `explicit`
+    /// (e.g. a `--config` argument) first, then the `FACE_VALIDATION_CONFIG`
+    /// environment variable; when neither is set the built-in defaults are used.
+    pub fn load(explicit: Option<String>) -> Result<Self, ConfigError> {
+        let path = explicit.or_else(|| std::env::var("FACE_VALIDATION_CONFIG").ok());
+        match path {
+            Some(path) => Self::from_file(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn from_file(path: &str) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(Path::new(path)).map_err(|source| {
+            ConfigError::Read {
+                path: path.to_string(),
+                source,
+            }
+        })?;
+        toml::from_str(&contents).map_err(|source| ConfigError::Parse {
+            path: path.to_string(),
+            source,
+        })
+    }
+}
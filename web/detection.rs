@@ -1,6 +1,7 @@
 use crate::{
     clustering::cluster_boxes,
-    types::{Detection, ProcessingError, CONF_THRESHOLD, INPUT_SIZE},
+    config::DetectionConfig,
+    types::{Detection, ProcessingError},
 };
 use image::DynamicImage;
 use lazy_static::lazy_static;
@@ -37,6 +38,7 @@ pub fn cleanup_old_buffers() {
 pub async fn process_image(
     image: DynamicImage,
     session: Arc<Session>, // Change the parameter type to Arc<Session>
+    config: DetectionConfig,
 ) -> Result<Vec<[i32; 4]>, ProcessingError> {
     let mut last_error = None;
 
@@ -44,8 +46,9 @@ pub async fn process_image(
         let image_clone = image.clone();
         let session_clone = Arc::clone(&session); // Clone the Arc, not the session directly
 
-        let processing =
-            tokio::spawn(async move { process_image_internal(image_clone, &session_clone) });
+        let processing = tokio::spawn(async move {
+            process_image_internal(image_clone, &session_clone, &config)
+        });
 
         match timeout(PROCESSING_TIMEOUT, processing).await {
             Ok(Ok(Ok(boxes))) => return Ok(boxes),
@@ -74,36 +77,48 @@ pub async fn process_image(
 fn process_image_internal(
     image: DynamicImage,
     session: &Session,
+    config: &DetectionConfig,
 ) -> Result<Vec<[i32; 4]>, ProcessingError> {
+    let start = std::time::Instant::now();
     let original_width = image.width();
     let original_height = image.height();
 
+    let input_size = config.input_size();
+
     // Convert image to RGB
     let rgb_image = image.to_rgb8();
 
     // Resize image
     let resized = image::imageops::resize(
         &rgb_image,
-        INPUT_SIZE.0,
-        INPUT_SIZE.1,
+        input_size.0,
+        input_size.1,
         image::imageops::FilterType::Triangle,
     );
 
     // Prepare input buffer
-    let input_data = prepare_input_buffer(&resized)?;
+    let input_data = prepare_input_buffer(&resized, input_size)?;
 
     // Run inference
-    let predictions = run_inference(session, input_data)?;
+    let predictions = run_inference(session, input_data, input_size)?;
 
-    let mut detections = process_predictions(predictions, original_width, original_height)?;
+    let mut detections =
+        process_predictions(predictions, original_width, original_height, config)?;
 
-    Ok(cluster_boxes(&mut detections))
+    let boxes = cluster_boxes(&mut detections, config);
+    tracing::debug!(
+        detections = boxes.len(),
+        elapsed_ms = start.elapsed().as_millis() as u64,
+        "clustered detections",
+    );
+    Ok(boxes)
 }
 
 fn prepare_input_buffer(
     resized: &image::ImageBuffer<image::Rgb<u8>, Vec<u8>>,
+    input_size: (u32, u32),
 ) -> Result<Vec<f32>, ProcessingError> {
-    let buffer_size = (INPUT_SIZE.0 * INPUT_SIZE.1 * 3) as usize;
+    let buffer_size = (input_size.0 * input_size.1 * 3) as usize;
 
     // Get or create buffer from cache
     let mut input_data = {
@@ -117,10 +132,10 @@ fn prepare_input_buffer(
 
     // Process channels with error recovery
     for c in 0..3 {
-        match process_channel_safely(resized, c) {
+        match process_channel_safely(resized, c, input_size) {
             Ok(channel_data) => input_data.extend(channel_data),
             Err(_) => {
-                input_data.extend(process_channel_fallback(resized, c));
+                input_data.extend(process_channel_fallback(resized, c, input_size));
             }
         }
     }
@@ -137,21 +152,23 @@ fn prepare_input_buffer(
 fn process_channel_safely(
     resized: &image::ImageBuffer<image::Rgb<u8>, Vec<u8>>,
     channel: usize,
+    input_size: (u32, u32),
 ) -> Result<Vec<f32>, ProcessingError> {
     #[cfg(target_arch = "x86_64")]
     if is_x86_feature_detected!("avx2") {
         return Ok(unsafe { process_channel_simd(resized.as_raw(), channel) });
     }
 
-    Ok(process_channel_fallback(resized, channel))
+    Ok(process_channel_fallback(resized, channel, input_size))
 }
 
 fn process_channel_fallback(
     resized: &image::ImageBuffer<image::Rgb<u8>, Vec<u8>>,
     channel: usize,
+    input_size: (u32, u32),
 ) -> Vec<f32> {
     let pixels = resized.as_raw();
-    let mut result = Vec::with_capacity((INPUT_SIZE.0 * INPUT_SIZE.1) as usize);
+    let mut result = Vec::with_capacity((input_size.0 * input_size.1) as usize);
 
     for i in (channel..pixels.len()).step_by(3) {
         result.push(pixels[i] as f32 / 255.0);
@@ -160,11 +177,26 @@ fn process_channel_fallback(
     result
 }
 
+/// Number of YOLOv8 anchors for a given input size: the sum of the grid cells
+/// at each detection stride (8, 16, 32). For the default 640x640 input this is
+/// 80*80 + 40*40 + 20*20 = 8400.
+fn anchor_count(input_size: (u32, u32)) -> usize {
+    [8u32, 16, 32]
+        .iter()
+        .map(|stride| {
+            let w = (input_size.0 / stride) as usize;
+            let h = (input_size.1 / stride) as usize;
+            w * h
+        })
+        .sum()
+}
+
 fn run_inference(
     session: &Session,
     input_data: Vec<f32>,
+    input_size: (u32, u32),
 ) -> Result<ndarray::Array2<f32>, ProcessingError> {
-    let shape = [1, 3, INPUT_SIZE.0 as usize, INPUT_SIZE.1 as usize];
+    let shape = [1, 3, input_size.0 as usize, input_size.1 as usize];
 
     // Create array from input data
     let array = Array::from_shape_vec(shape, input_data)
@@ -191,15 +223,20 @@ fn run_inference(
 
     let output_view = output_tensor.view();
 
+    // YOLOv8 emits one prediction per anchor across the P3/P4/P5 strides
+    // (8, 16, 32); the anchor count scales with the configured input size,
+    // so derive it rather than hardcoding the 640x640 value of 8400.
+    let anchors = anchor_count(input_size);
+
     // Reshape predictions
     output_view
         .to_owned()
-        .into_shape((1, 5, 8400))
+        .into_shape((1, 5, anchors))
         .map_err(|e| ProcessingError::Internal(format!("Failed to reshape output: {}", e)))?
         .permuted_axes([2, 1, 0])
         .as_standard_layout()
         .to_owned()
-        .into_shape((8400, 5))
+        .into_shape((anchors, 5))
         .map_err(|e| ProcessingError::Internal(format!("Failed to reshape predictions: {}", e)))
 }
 
@@ -207,18 +244,21 @@ fn process_predictions(
     predictions: ndarray::Array2<f32>,
     original_width: u32,
     original_height: u32,
+    config: &DetectionConfig,
 ) -> Result<Vec<Detection>, ProcessingError> {
+    let input_size = config.input_size();
     let detections: Vec<Detection> = predictions
         .axis_iter(ndarray::Axis(0))
         .par_bridge()
         .filter_map(|prediction| {
             let confidence = prediction[4];
-            if confidence >= CONF_THRESHOLD {
+            if confidence >= config.conf_threshold {
                 Some(create_detection(
                     prediction,
                     original_width,
                     original_height,
                     confidence,
+                    input_size,
                 ))
             } else {
                 None
@@ -296,17 +336,18 @@ fn create_detection(
     original_width: u32,
     original_height: u32,
     confidence: f32,
+    input_size: (u32, u32),
 ) -> Detection {
     let pred_vec = Vector4::new(prediction[0], prediction[1], prediction[2], prediction[3]);
 
-    let input_size = Vector4::new(
-        INPUT_SIZE.0 as f32,
-        INPUT_SIZE.1 as f32,
-        INPUT_SIZE.0 as f32,
-        INPUT_SIZE.1 as f32,
+    let input_dims = Vector4::new(
+        input_size.0 as f32,
+        input_size.1 as f32,
+        input_size.0 as f32,
+        input_size.1 as f32,
     );
 
-    let abs_coords = pred_vec.component_mul(&input_size);
+    let abs_coords = pred_vec.component_mul(&input_dims);
     let [abs_x_center, abs_y_center, abs_width, abs_height] =
         [abs_coords[0], abs_coords[1], abs_coords[2], abs_coords[3]];
 
@@ -317,8 +358,8 @@ fn create_detection(
     let corners_max = center + half_sizes;
 
     let scale = Vector2::new(
-        original_width as f32 / INPUT_SIZE.0 as f32,
-        original_height as f32 / INPUT_SIZE.1 as f32,
+        original_width as f32 / input_size.0 as f32,
+        original_height as f32 / input_size.1 as f32,
     );
 
     let scaled_min = (corners_min.component_mul(&scale)).map(|x| x.round() as i32);
@@ -1,12 +1,19 @@
 use crate::{
+    auth::Claims,
+    batch,
     cache::get_or_create_session,
+    config::MediaConfig,
     detection::process_image,
-    types::{ApiResponse, AppState, ErrorCode, ErrorResponse, ValidationResponse},
+    recognition, result_cache,
+    types::{
+        ApiResponse, AppState, CompareResponse, ErrorCode, ErrorResponse, ValidationResponse,
+    },
 };
 use actix_multipart::Multipart;
-use actix_web::{post, web, Error, HttpRequest, HttpResponse};
+use actix_web::{get, http::StatusCode, post, web, Error, HttpMessage, HttpRequest, HttpResponse};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
-use futures_util::{StreamExt, TryStreamExt};
+use futures_util::{stream, StreamExt, TryStreamExt};
+use tracing::Instrument;
 
 fn create_error_response(code: ErrorCode, message: &str, details: Option<&str>) -> ApiResponse {
     ApiResponse::Error(ErrorResponse {
@@ -16,28 +23,64 @@ fn create_error_response(code: ErrorCode, message: &str, details: Option<&str>)
     })
 }
 
+/// Detail string for a [`ErrorCode::FileTooLarge`] rejection, reflecting the
+/// configured `media.max_upload_bytes` rather than a hardcoded figure.
+fn max_size_detail(max_size: usize) -> String {
+    format!("Maximum file size is {} bytes", max_size)
+}
+
 #[post("/validate-face")]
 pub async fn validate_face(
     req: HttpRequest,
     payload: web::Payload,
     data: web::Data<AppState>,
 ) -> Result<HttpResponse, Error> {
-    const MAX_SIZE: usize = 10 * 1024 * 1024; // 10MB limit
-    const ALLOWED_MIME_TYPES: [&str; 4] =
-        ["image/jpeg", "image/png", "image/webp", "application/json"];
-
     let content_type = req
         .headers()
         .get("content-type")
         .and_then(|ct| ct.to_str().ok())
-        .unwrap_or("");
+        .unwrap_or("")
+        .to_string();
+
+    // Embeddings are opt-in per request via `?embedding=true`.
+    let want_embedding = req
+        .query_string()
+        .split('&')
+        .any(|pair| matches!(pair, "embedding" | "embedding=true" | "embedding=1"));
+
+    let span = tracing::info_span!("validate_face", content_type = content_type_kind(&content_type));
+    run_validate_face(content_type, payload, data, want_embedding)
+        .instrument(span)
+        .await
+}
+
+/// Classify the request's content type into the path taken through the handler,
+/// used as a span field so latency can be attributed per upload style.
+fn content_type_kind(content_type: &str) -> &'static str {
+    if content_type.starts_with("multipart/form-data") {
+        "multipart"
+    } else if content_type.starts_with("application/json") {
+        "json"
+    } else {
+        "raw"
+    }
+}
+
+async fn run_validate_face(
+    content_type: String,
+    payload: web::Payload,
+    data: web::Data<AppState>,
+    want_embedding: bool,
+) -> Result<HttpResponse, Error> {
+    let media = &data.config.media;
+    let max_size = media.max_upload_bytes;
 
     let bytes = if content_type.starts_with("multipart/form-data") {
-        process_multipart(content_type, payload, &ALLOWED_MIME_TYPES, MAX_SIZE).await?
+        process_multipart(&content_type, payload, media, max_size).await?
     } else if content_type.starts_with("application/json") {
-        process_json_payload(payload, MAX_SIZE).await?
-    } else if ALLOWED_MIME_TYPES.contains(&content_type) {
-        process_raw_file(payload, MAX_SIZE).await?
+        process_json_payload(payload, max_size).await?
+    } else if media.is_allowed(&content_type) {
+        process_raw_file(payload, max_size).await?
     } else {
         return Ok(
             HttpResponse::BadRequest().json(ApiResponse::Error(ErrorResponse {
@@ -48,9 +91,10 @@ pub async fn validate_face(
         );
     };
 
-    process_image_bytes(bytes, data).await
+    process_image_bytes(bytes, data, want_embedding).await
 }
 
+#[tracing::instrument(skip_all)]
 async fn process_json_payload(
     mut payload: web::Payload,
     max_size: usize,
@@ -63,7 +107,7 @@ async fn process_json_payload(
             return Err(actix_web::error::ErrorBadRequest(create_error_response(
                 ErrorCode::FileTooLarge,
                 "File too large",
-                Some("Maximum file size is 10MB"),
+                Some(&max_size_detail(max_size)),
             )));
         }
         bytes.extend_from_slice(&chunk);
@@ -90,10 +134,11 @@ async fn process_json_payload(
     }
 }
 
+#[tracing::instrument(skip_all)]
 async fn process_multipart(
     content_type: &str,
     payload: web::Payload,
-    allowed_mime_types: &[&str],
+    media: &MediaConfig,
     max_size: usize,
 ) -> Result<Vec<u8>, actix_web::Error> {
     let mut headers = actix_web::http::header::HeaderMap::new();
@@ -111,7 +156,7 @@ async fn process_multipart(
 
     if let Some(mut field) = multipart.try_next().await? {
         if let Some(content_type) = field.content_type() {
-            if !allowed_mime_types.contains(&content_type.to_string().as_str()) {
+            if !media.is_allowed(&content_type.to_string()) {
                 return Err(actix_web::error::ErrorBadRequest(create_error_response(
                     ErrorCode::UnsupportedFileType,
                     "Invalid file type",
@@ -126,7 +171,7 @@ async fn process_multipart(
                 return Err(actix_web::error::ErrorBadRequest(create_error_response(
                     ErrorCode::FileTooLarge,
                     "File too large",
-                    Some("Maximum file size is 10MB"),
+                    Some(&max_size_detail(max_size)),
                 )));
             }
             bytes.extend_from_slice(&chunk);
@@ -144,6 +189,7 @@ async fn process_multipart(
     )))
 }
 
+#[tracing::instrument(skip_all)]
 async fn process_raw_file(mut payload: web::Payload, max_size: usize) -> Result<Vec<u8>, Error> {
     let mut bytes = Vec::with_capacity(max_size / 2);
     while let Some(chunk) = payload.next().await {
@@ -152,7 +198,7 @@ async fn process_raw_file(mut payload: web::Payload, max_size: usize) -> Result<
             return Err(actix_web::error::ErrorBadRequest(create_error_response(
                 ErrorCode::FileTooLarge,
                 "File too large",
-                Some("Maximum file size is 10MB"),
+                Some(&max_size_detail(max_size)),
             )));
         }
         bytes.extend_from_slice(&chunk);
@@ -172,59 +218,613 @@ async fn process_raw_file(mut payload: web::Payload, max_size: usize) -> Result<
 async fn process_image_bytes(
     bytes: Vec<u8>,
     data: web::Data<AppState>,
+    want_embedding: bool,
+) -> Result<HttpResponse, Error> {
+    let response = match validate_image_bytes(bytes, &data, want_embedding).await {
+        Ok(response) => HttpResponse::Ok().json(ApiResponse::Success(response)),
+        Err(error) => {
+            HttpResponse::build(status_for(&error.code)).json(ApiResponse::Error(error))
+        }
+    };
+    Ok(response)
+}
+
+/// Run the full validation pipeline over a single image's bytes, returning the
+/// structured response or a typed error. Shared by `/validate-face` and the
+/// batch endpoints so both paths honour the result cache, decompression-bomb
+/// guard, and concurrency limit identically. When `want_embedding` is set and a
+/// single face is detected the recognition embedding is attached as well.
+#[tracing::instrument(skip_all)]
+async fn validate_image_bytes(
+    bytes: Vec<u8>,
+    data: &web::Data<AppState>,
+    want_embedding: bool,
+) -> Result<ValidationResponse, ErrorResponse> {
+    // Identical uploads (for the same model and thresholds) skip inference
+    // entirely. Embedding requests get a distinct key so a cached plain result
+    // is never returned in their place.
+    let mut cache_key =
+        result_cache::cache_key(&bytes, &data.config.model.path, &data.config.detection);
+    if want_embedding {
+        cache_key.push_str(":embed:");
+        cache_key.push_str(&data.config.recognition.path);
+    }
+    if let Some(cached) = result_cache::get(&cache_key) {
+        return Ok(cached);
+    }
+
+    let (img, boxes) = decode_and_detect(bytes, data).await?;
+
+    let face_count = boxes.len();
+    let (is_valid, message) = match face_count {
+        0 => (false, "No faces detected".to_string()),
+        1 => (true, "Valid single face detected".to_string()),
+        n => (false, format!("Multiple faces detected: {}", n)),
+    };
+
+    let embedding = if want_embedding && face_count == 1 {
+        Some(compute_embedding(&img, boxes[0], data).await?)
+    } else {
+        None
+    };
+    drop(img);
+
+    let response = ValidationResponse {
+        is_valid,
+        face_count,
+        message,
+        embedding,
+    };
+    result_cache::insert(cache_key, &response);
+
+    Ok(response)
+}
+
+/// Shared front half of the pipeline: session lookup, decompression-bomb guard,
+/// concurrency permit, decode, and detection. Returns the decoded image (kept
+/// around for optional cropping) alongside the detected boxes.
+async fn decode_and_detect(
+    bytes: Vec<u8>,
+    data: &web::Data<AppState>,
+) -> Result<(image::DynamicImage, Vec<[i32; 4]>), ErrorResponse> {
+    let session = get_or_create_session(&data.environment, &data.config.model).map_err(|e| {
+        ErrorResponse {
+            code: ErrorCode::InternalError,
+            message: "Failed to initialize face detection".to_string(),
+            details: Some(e.to_string()),
+        }
+    })?;
+
+    // Cheaply read just the header dimensions and reject decompression bombs
+    // before committing to the expensive full decode below.
+    match image::io::Reader::new(std::io::Cursor::new(&bytes))
+        .with_guessed_format()
+        .ok()
+        .and_then(|reader| reader.into_dimensions().ok())
+    {
+        Some((width, height)) => {
+            data.config
+                .media
+                .check_dimensions(width, height)
+                .map_err(|reason| ErrorResponse {
+                    code: ErrorCode::ImageTooLarge,
+                    message: "Image dimensions exceed allowed limits".to_string(),
+                    details: Some(reason),
+                })?;
+        }
+        None => {
+            return Err(ErrorResponse {
+                code: ErrorCode::InvalidImageFormat,
+                message: "Failed to decode image".to_string(),
+                details: Some("Could not determine image dimensions".to_string()),
+            });
+        }
+    }
+
+    // Bound how many decode+inference pipelines run at once across all requests.
+    let _permit = data
+        .semaphore
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|e| ErrorResponse {
+            code: ErrorCode::InternalError,
+            message: "Failed to acquire processing slot".to_string(),
+            details: Some(e.to_string()),
+        })?;
+
+    let img = web::block(move || image::load_from_memory(&bytes))
+        .instrument(tracing::debug_span!("decode"))
+        .await
+        .map_err(|e| ErrorResponse {
+            code: ErrorCode::InternalError,
+            message: "Failed to decode image".to_string(),
+            details: Some(e.to_string()),
+        })?
+        .map_err(|e| ErrorResponse {
+            code: ErrorCode::InvalidImageFormat,
+            message: "Failed to decode image".to_string(),
+            details: Some(e.to_string()),
+        })?;
+
+    let boxes = process_image(img.clone(), session, data.config.detection)
+        .instrument(tracing::debug_span!("inference"))
+        .await
+        .map_err(|e| ErrorResponse {
+            code: ErrorCode::ProcessingError,
+            message: "Failed to process image".to_string(),
+            details: Some(e.to_string()),
+        })?;
+
+    Ok((img, boxes))
+}
+
+/// Run the recognition model over the cropped face and return its unit-length
+/// embedding. Errors when no recognition model is configured.
+async fn compute_embedding(
+    img: &image::DynamicImage,
+    bbox: [i32; 4],
+    data: &web::Data<AppState>,
+) -> Result<Vec<f32>, ErrorResponse> {
+    let recognition = &data.config.recognition;
+    if !recognition.configured() {
+        return Err(ErrorResponse {
+            code: ErrorCode::InternalError,
+            message: "Embedding requested but no recognition model is configured".to_string(),
+            details: None,
+        });
+    }
+
+    let session = get_or_create_session(
+        &data.environment,
+        &recognition.model_config(&data.config.model),
+    )
+    .map_err(|e| ErrorResponse {
+        code: ErrorCode::InternalError,
+        message: "Failed to initialize face recognition".to_string(),
+        details: Some(e.to_string()),
+    })?;
+
+    // Share the same processing-slot bound as decode+detection so embedding
+    // inference can't run unbounded alongside it.
+    let _permit = data
+        .semaphore
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|e| ErrorResponse {
+            code: ErrorCode::InternalError,
+            message: "Failed to acquire processing slot".to_string(),
+            details: Some(e.to_string()),
+        })?;
+
+    let img = img.clone();
+    let config = recognition.clone();
+    web::block(move || recognition::embed_face(&img, bbox, &session, &config))
+        .instrument(tracing::debug_span!("embed"))
+        .await
+        .map_err(|e| ErrorResponse {
+            code: ErrorCode::InternalError,
+            message: "Failed to extract embedding".to_string(),
+            details: Some(e.to_string()),
+        })?
+        .map_err(|e| ErrorResponse {
+            code: ErrorCode::ProcessingError,
+            message: "Failed to extract embedding".to_string(),
+            details: Some(e.to_string()),
+        })
+}
+
+/// Decode an image, require exactly one face, and return its embedding. Backs
+/// the `/compare-faces` endpoint.
+async fn embed_single_face(
+    bytes: Vec<u8>,
+    data: &web::Data<AppState>,
+) -> Result<Vec<f32>, ErrorResponse> {
+    let (img, boxes) = decode_and_detect(bytes, data).await?;
+    match boxes.len() {
+        1 => compute_embedding(&img, boxes[0], data).await,
+        0 => Err(ErrorResponse {
+            code: ErrorCode::ProcessingError,
+            message: "No face detected".to_string(),
+            details: Some("Each image must contain exactly one face".to_string()),
+        }),
+        n => Err(ErrorResponse {
+            code: ErrorCode::ProcessingError,
+            message: format!("Multiple faces detected: {}", n),
+            details: Some("Each image must contain exactly one face".to_string()),
+        }),
+    }
+}
+
+/// Map a typed [`ErrorCode`] onto the HTTP status used for the single-image
+/// endpoint, preserving the status codes the handler returned before the shared
+/// pipeline was extracted.
+fn status_for(code: &ErrorCode) -> StatusCode {
+    match code {
+        ErrorCode::ImageTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+        ErrorCode::ProcessingError | ErrorCode::InternalError => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+#[post("/validate-batch")]
+pub async fn validate_batch(
+    req: HttpRequest,
+    payload: web::Payload,
+    data: web::Data<AppState>,
 ) -> Result<HttpResponse, Error> {
-    let session = match get_or_create_session(&data.environment, &data.model_path) {
-        Ok(session) => session,
-        Err(e) => {
-            return Ok(HttpResponse::InternalServerError().json(ApiResponse::Error(
-                ErrorResponse {
-                    code: ErrorCode::InternalError,
-                    message: "Failed to initialize face detection".to_string(),
-                    details: Some(e.to_string()),
-                },
+    let media = &data.config.media;
+    let max_size = media.max_upload_bytes;
+
+    let content_type = req
+        .headers()
+        .get("content-type")
+        .and_then(|ct| ct.to_str().ok())
+        .unwrap_or("");
+
+    let max_items = data.config.batch.max_items;
+    let images = if content_type.starts_with("multipart/form-data") {
+        collect_batch_multipart(content_type, payload, media, max_size, max_items).await?
+    } else if content_type.starts_with("application/json") {
+        collect_batch_json(payload, max_size, max_items).await?
+    } else {
+        return Ok(
+            HttpResponse::BadRequest().json(ApiResponse::Error(ErrorResponse {
+                code: ErrorCode::InvalidContentType,
+                message: "Unsupported content type".to_string(),
+                details: Some(
+                    "Use multipart/form-data with repeated fields or a JSON array of base64 images"
+                        .to_string(),
+                ),
+            })),
+        );
+    };
+
+    if images.is_empty() {
+        return Err(actix_web::error::ErrorBadRequest(create_error_response(
+            ErrorCode::NoFileProvided,
+            "No images provided",
+            Some("Request must include at least one image"),
+        )));
+    }
+
+    if max_items != 0 && images.len() > max_items {
+        return Err(actix_web::error::ErrorBadRequest(create_error_response(
+            ErrorCode::FileTooLarge,
+            "Too many images in batch",
+            Some(&format!("Maximum batch size is {} images", max_items)),
+        )));
+    }
+
+    // Small batches are cheap enough to answer inline; larger ones are handed to
+    // a background task and polled through `/validate-batch/{id}`.
+    if images.len() <= data.config.batch.inline_threshold {
+        let results = run_batch_inline(images, &data).await;
+        let failed = results
+            .iter()
+            .filter(|r| matches!(r, ApiResponse::Error(_)))
+            .count();
+        return Ok(HttpResponse::Ok().json(batch::BatchStatusResponse {
+            job_id: None,
+            status: batch::JobStatus::Completed,
+            total: results.len(),
+            completed: results.len(),
+            failed,
+            results,
+        }));
+    }
+
+    let caller_id = req
+        .extensions()
+        .get::<Claims>()
+        .and_then(|claims| claims.caller_id.clone());
+    let job_id = batch::create_job(images.len(), caller_id);
+    let total = images.len();
+    let accepted = batch::BatchAccepted {
+        job_id: job_id.clone(),
+        status: batch::JobStatus::Running,
+        total,
+    };
+
+    let data = data.clone();
+    actix_web::rt::spawn(async move {
+        run_batch_job(job_id, images, data).await;
+    });
+
+    Ok(HttpResponse::Accepted().json(accepted))
+}
+
+#[get("/validate-batch/{id}")]
+pub async fn validate_batch_status(
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> Result<HttpResponse, Error> {
+    let caller_id = req
+        .extensions()
+        .get::<Claims>()
+        .and_then(|claims| claims.caller_id.clone());
+    match batch::snapshot(&path.into_inner(), caller_id.as_deref()) {
+        Some(snapshot) => Ok(HttpResponse::Ok().json(snapshot)),
+        None => Ok(
+            HttpResponse::NotFound().json(ApiResponse::Error(ErrorResponse {
+                code: ErrorCode::NoFileProvided,
+                message: "Unknown batch job".to_string(),
+                details: Some("The job id is invalid or has expired".to_string()),
+            })),
+        ),
+    }
+}
+
+/// Validate every image in input order, bounded by the configured concurrency,
+/// and collect the per-image outcomes.
+async fn run_batch_inline(images: Vec<Vec<u8>>, data: &web::Data<AppState>) -> Vec<ApiResponse> {
+    let concurrency = data.config.batch.concurrency();
+    let mut results: Vec<Option<ApiResponse>> = (0..images.len()).map(|_| None).collect();
+
+    let mut pending = stream::iter(images.into_iter().enumerate())
+        .map(|(index, bytes)| async move { (index, validate_one(bytes, data).await) })
+        .buffer_unordered(concurrency);
+
+    while let Some((index, outcome)) = pending.next().await {
+        results[index] = Some(outcome);
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+/// Background worker for large batches: runs the same bounded pipeline as the
+/// inline path but records each outcome into the shared job state as it lands so
+/// callers can poll progress.
+async fn run_batch_job(job_id: String, images: Vec<Vec<u8>>, data: web::Data<AppState>) {
+    let concurrency = data.config.batch.concurrency();
+
+    let mut pending = stream::iter(images.into_iter().enumerate())
+        .map(|(index, bytes)| {
+            let data = data.clone();
+            async move { (index, validate_one(bytes, &data).await) }
+        })
+        .buffer_unordered(concurrency);
+
+    while let Some((index, outcome)) = pending.next().await {
+        batch::record(&job_id, index, outcome);
+    }
+
+    batch::complete_job(&job_id);
+}
+
+async fn validate_one(bytes: Vec<u8>, data: &web::Data<AppState>) -> ApiResponse {
+    match validate_image_bytes(bytes, data, false).await {
+        Ok(response) => ApiResponse::Success(response),
+        Err(error) => ApiResponse::Error(error),
+    }
+}
+
+/// Collect image fields from a multipart batch body. `max_items` bounds the
+/// number of image fields accepted; a `0` disables the check. Like the
+/// per-field `max_size` cap, it is enforced while the body is read (as each
+/// field finishes buffering) rather than after the whole body, so a client
+/// cannot force the server to hold thousands of fields in memory before the
+/// rejection is returned.
+async fn collect_batch_multipart(
+    content_type: &str,
+    payload: web::Payload,
+    media: &MediaConfig,
+    max_size: usize,
+    max_items: usize,
+) -> Result<Vec<Vec<u8>>, actix_web::Error> {
+    let mut headers = actix_web::http::header::HeaderMap::new();
+    if let Ok(header_value) = content_type.parse() {
+        headers.insert(actix_web::http::header::CONTENT_TYPE, header_value);
+    } else {
+        return Err(actix_web::error::ErrorBadRequest(create_error_response(
+            ErrorCode::InvalidContentType,
+            "Invalid content-type header",
+            None,
+        )));
+    }
+
+    let mut multipart = Multipart::new(&headers, payload);
+    let mut images = Vec::new();
+
+    while let Some(mut field) = multipart.try_next().await? {
+        if let Some(content_type) = field.content_type() {
+            if !media.is_allowed(&content_type.to_string()) {
+                return Err(actix_web::error::ErrorBadRequest(create_error_response(
+                    ErrorCode::UnsupportedFileType,
+                    "Invalid file type",
+                    Some("Only JPEG, PNG and WebP are supported"),
+                )));
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(max_size / 2);
+        while let Some(chunk) = field.try_next().await? {
+            if bytes.len() + chunk.len() > max_size {
+                return Err(actix_web::error::ErrorBadRequest(create_error_response(
+                    ErrorCode::FileTooLarge,
+                    "File too large",
+                    Some(&max_size_detail(max_size)),
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        if !bytes.is_empty() {
+            images.push(bytes);
+        }
+
+        if max_items != 0 && images.len() > max_items {
+            return Err(actix_web::error::ErrorBadRequest(create_error_response(
+                ErrorCode::FileTooLarge,
+                "Too many images in batch",
+                Some(&format!("Maximum batch size is {} images", max_items)),
             )));
         }
+    }
+
+    Ok(images)
+}
+
+/// Collect and base64-decode a JSON batch body. `max_items` bounds the number
+/// of images accepted (a `0` disables the check) and is enforced as each
+/// entry is decoded, so a client can't force the server to decode an
+/// unbounded array before the count is rejected.
+async fn collect_batch_json(
+    mut payload: web::Payload,
+    max_size: usize,
+    max_items: usize,
+) -> Result<Vec<Vec<u8>>, actix_web::Error> {
+    // A batch envelope can carry many images, so allow it to be several times the
+    // per-image limit before refusing.
+    let envelope_limit = max_size.saturating_mul(8);
+    let mut bytes = Vec::with_capacity(max_size / 2);
+
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+        if bytes.len() + chunk.len() > envelope_limit {
+            return Err(actix_web::error::ErrorBadRequest(create_error_response(
+                ErrorCode::FileTooLarge,
+                "Batch payload too large",
+                None,
+            )));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&bytes)?;
+
+    // Accept either a bare array of base64 strings or an `{ "images": [...] }`
+    // envelope, mirroring the single-image endpoint's `image` field.
+    let array = match &json {
+        serde_json::Value::Array(items) => Some(items),
+        serde_json::Value::Object(_) => json.get("images").and_then(|v| v.as_array()),
+        _ => None,
     };
 
-    let img = match web::block(move || image::load_from_memory(&bytes)).await? {
-        Ok(img) => img,
-        Err(e) => {
-            return Ok(
-                HttpResponse::BadRequest().json(ApiResponse::Error(ErrorResponse {
-                    code: ErrorCode::InvalidImageFormat,
-                    message: "Failed to decode image".to_string(),
-                    details: Some(e.to_string()),
-                })),
-            );
+    let array = array.ok_or_else(|| {
+        actix_web::error::ErrorBadRequest(create_error_response(
+            ErrorCode::NoFileProvided,
+            "No images in JSON",
+            Some("Request must include a JSON array of base64 encoded images"),
+        ))
+    })?;
+
+    let mut images = Vec::with_capacity(array.len());
+    for item in array {
+        let base64_str = item.as_str().ok_or_else(|| {
+            actix_web::error::ErrorBadRequest(create_error_response(
+                ErrorCode::InvalidImageFormat,
+                "Invalid image entry",
+                Some("Each image must be a base64 encoded string"),
+            ))
+        })?;
+        let decoded = STANDARD.decode(base64_str).map_err(|_| {
+            actix_web::error::ErrorBadRequest(create_error_response(
+                ErrorCode::InvalidImageFormat,
+                "Invalid base64 image data",
+                None,
+            ))
+        })?;
+        if decoded.len() > max_size {
+            return Err(actix_web::error::ErrorBadRequest(create_error_response(
+                ErrorCode::FileTooLarge,
+                "File too large",
+                Some(&max_size_detail(max_size)),
+            )));
         }
+        images.push(decoded);
+
+        if max_items != 0 && images.len() > max_items {
+            return Err(actix_web::error::ErrorBadRequest(create_error_response(
+                ErrorCode::FileTooLarge,
+                "Too many images in batch",
+                Some(&format!("Maximum batch size is {} images", max_items)),
+            )));
+        }
+    }
+
+    Ok(images)
+}
+
+#[post("/compare-faces")]
+pub async fn compare_faces(
+    req: HttpRequest,
+    payload: web::Payload,
+    data: web::Data<AppState>,
+) -> Result<HttpResponse, Error> {
+    let media = &data.config.media;
+    let max_size = media.max_upload_bytes;
+
+    let content_type = req
+        .headers()
+        .get("content-type")
+        .and_then(|ct| ct.to_str().ok())
+        .unwrap_or("");
+
+    // Exactly two images are ever needed; cap collection there so a client
+    // can't force the server to buffer an unbounded number of fields before
+    // the "exactly two" check below runs.
+    let images = if content_type.starts_with("multipart/form-data") {
+        collect_batch_multipart(content_type, payload, media, max_size, 2).await?
+    } else if content_type.starts_with("application/json") {
+        collect_batch_json(payload, max_size, 2).await?
+    } else {
+        return Ok(
+            HttpResponse::BadRequest().json(ApiResponse::Error(ErrorResponse {
+                code: ErrorCode::InvalidContentType,
+                message: "Unsupported content type".to_string(),
+                details: Some(
+                    "Send two images as multipart fields or a JSON array of two base64 images"
+                        .to_string(),
+                ),
+            })),
+        );
     };
 
-    let result = process_image(img.clone(), session).await;
-    drop(img);
+    if images.len() != 2 {
+        return Err(actix_web::error::ErrorBadRequest(create_error_response(
+            ErrorCode::NoFileProvided,
+            "Exactly two images are required",
+            Some("Provide two images to compare"),
+        )));
+    }
 
-    match result {
-        Ok(boxes) => {
-            let face_count = boxes.len();
-            let (is_valid, message) = match face_count {
-                0 => (false, "No faces detected".to_string()),
-                1 => (true, "Valid single face detected".to_string()),
-                n => (false, format!("Multiple faces detected: {}", n)),
-            };
-
-            Ok(
-                HttpResponse::Ok().json(ApiResponse::Success(ValidationResponse {
-                    is_valid,
-                    face_count,
-                    message,
-                })),
-            )
-        }
-        Err(e) => Ok(
+    if !data.config.recognition.configured() {
+        return Ok(
             HttpResponse::InternalServerError().json(ApiResponse::Error(ErrorResponse {
-                code: ErrorCode::ProcessingError,
-                message: "Failed to process image".to_string(),
-                details: Some(e.to_string()),
+                code: ErrorCode::InternalError,
+                message: "Face comparison requires a recognition model".to_string(),
+                details: None,
             })),
-        ),
+        );
     }
+
+    let mut images = images.into_iter();
+    let first = images.next().unwrap();
+    let second = images.next().unwrap();
+
+    let embedding_a = match embed_single_face(first, &data).await {
+        Ok(embedding) => embedding,
+        Err(error) => {
+            return Ok(HttpResponse::build(status_for(&error.code)).json(ApiResponse::Error(error)))
+        }
+    };
+    let embedding_b = match embed_single_face(second, &data).await {
+        Ok(embedding) => embedding,
+        Err(error) => {
+            return Ok(HttpResponse::build(status_for(&error.code)).json(ApiResponse::Error(error)))
+        }
+    };
+
+    let similarity = recognition::cosine_similarity(&embedding_a, &embedding_b);
+    let threshold = data.config.recognition.match_threshold;
+
+    Ok(HttpResponse::Ok().json(CompareResponse {
+        similarity,
+        is_match: similarity >= threshold,
+        threshold,
+    }))
 }
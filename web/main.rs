@@ -1,20 +1,41 @@
+mod auth;
+mod batch;
 mod cache;
 mod clustering;
+mod config;
 mod detection;
 mod handlers;
+mod recognition;
+mod result_cache;
+mod telemetry;
 mod types;
 
 use actix_web::{middleware, web, App, HttpServer};
-use handlers::validate_face;
+use auth::HmacAuth;
+use config::Config;
+use handlers::{compare_faces, validate_batch, validate_batch_status, validate_face};
 use ort::Environment;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Semaphore;
 use tokio::time; // Add this
+use tracing_actix_web::TracingLogger;
 use types::AppState;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    let config = Arc::new(Config::load(config_path_from_args()).unwrap_or_else(|e| {
+        eprintln!("Failed to load configuration: {}", e);
+        std::process::exit(1);
+    }));
+
+    // `mint-token` is an offline helper for issuing short-lived bearer tokens; it
+    // never starts the server.
+    if std::env::args().nth(1).as_deref() == Some("mint-token") {
+        return run_mint_token(&config);
+    }
+
+    telemetry::init(&config.telemetry);
 
     let environment = Arc::new(
         Environment::builder()
@@ -24,22 +45,33 @@ async fn main() -> std::io::Result<()> {
             .unwrap(),
     );
 
-    let model_path = "models/yolo11n_9ir_640_hface.onnx".to_string();
+    let bind_address = config.server.address.clone();
+    let keep_alive = config.server.keep_alive();
+    let request_timeout = config.server.request_timeout();
+
+    // Shared across workers so the cap bounds total in-flight inference, not
+    // per-worker inference.
+    let semaphore = Arc::new(Semaphore::new(config.batch.concurrency()));
 
     let server = HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(AppState {
                 environment: environment.clone(),
-                model_path: model_path.clone(),
+                config: config.clone(),
+                semaphore: semaphore.clone(),
             }))
-            .wrap(middleware::Logger::new("%r %s %D ms"))
+            .wrap(HmacAuth::new(&config.auth.secret))
+            .wrap(TracingLogger::default())
             .wrap(middleware::Compress::default())
             .wrap(middleware::NormalizePath::trim())
             .service(validate_face)
+            .service(validate_batch)
+            .service(validate_batch_status)
+            .service(compare_faces)
     })
-    .keep_alive(Duration::from_secs(30))
-    .client_request_timeout(Duration::from_secs(60))
-    .bind("127.0.0.1:8080")?;
+    .keep_alive(keep_alive)
+    .client_request_timeout(request_timeout)
+    .bind(bind_address)?;
 
     let cleanup_interval = Duration::from_secs(3600); // Every hour
     let cleanup_task = tokio::spawn(async move {
@@ -48,6 +80,8 @@ async fn main() -> std::io::Result<()> {
             interval.tick().await;
             cache::cleanup_expired_sessions();
             detection::cleanup_old_buffers();
+            result_cache::cleanup_expired_results();
+            batch::cleanup_expired_jobs();
         }
     });
 
@@ -70,10 +104,65 @@ async fn main() -> std::io::Result<()> {
             println!("Shutting down...");
             detection::cleanup_input_buffer_cache();
             cache::cleanup_session_cache();
+            result_cache::cleanup_result_cache();
+            batch::cleanup_job_cache();
             cleanup_task.abort();
+            telemetry::shutdown();
             time::sleep(Duration::from_secs(1)).await;
         }
     }
 
     Ok(())
 }
+
+/// Extract the configuration file path from `--config <path>` / `--config=<path>`,
+/// returning `None` when the flag is absent so loading falls back to the
+/// `FACE_VALIDATION_CONFIG` environment variable or the built-in defaults.
+fn config_path_from_args() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.to_string());
+        }
+        if arg == "--config" {
+            return args.next();
+        }
+    }
+    None
+}
+
+/// Handle the `mint-token` subcommand: issue a short-lived bearer token signed
+/// with the configured secret. Accepts `--caller <id>` and `--ttl <seconds>`,
+/// the latter defaulting to `auth.token_ttl_secs`.
+fn run_mint_token(config: &Config) -> std::io::Result<()> {
+    if !config.auth.enabled() {
+        eprintln!("No auth.secret configured; cannot mint a token");
+        std::process::exit(1);
+    }
+
+    let mut caller: Option<String> = None;
+    let mut ttl = config.auth.token_ttl_secs;
+
+    let mut args = std::env::args().skip(2);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--caller" => caller = args.next(),
+            "--ttl" => {
+                if let Some(value) = args.next().and_then(|v| v.parse().ok()) {
+                    ttl = value;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let expiry = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+        + ttl;
+
+    let token = auth::mint(&config.auth.secret, expiry, caller.as_deref());
+    println!("{}", token);
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use crate::config::RecognitionConfig;
+use crate::types::ProcessingError;
+use image::DynamicImage;
+use ndarray::{Array, CowArray};
+use ort::{Session, Value};
+
+/// Extract an L2-normalized embedding for the face at `bbox` using the cached
+/// recognition session. The detected box is cropped from the original image,
+/// resized to the model's input size, laid out as a CHW float tensor, and the
+/// raw output vector normalized to unit length so two embeddings can be compared
+/// with a plain dot product.
+pub fn embed_face(
+    image: &DynamicImage,
+    bbox: [i32; 4],
+    session: &Session,
+    config: &RecognitionConfig,
+) -> Result<Vec<f32>, ProcessingError> {
+    let crop = crop_to_bbox(image, bbox);
+    let (width, height) = config.input_size();
+    let resized = image::imageops::resize(
+        &crop.to_rgb8(),
+        width,
+        height,
+        image::imageops::FilterType::Triangle,
+    );
+
+    let pixels = resized.as_raw();
+    let mut input = Vec::with_capacity((width * height * 3) as usize);
+    for channel in 0..3 {
+        for i in (channel..pixels.len()).step_by(3) {
+            input.push(pixels[i] as f32 / 255.0);
+        }
+    }
+
+    let shape = [1, 3, height as usize, width as usize];
+    let array = Array::from_shape_vec(shape, input)
+        .map_err(|e| ProcessingError::Internal(format!("Failed to create input array: {}", e)))?;
+    let array = array.as_standard_layout().to_owned();
+    let cow_array = CowArray::from(array).into_dyn();
+
+    let input_tensor = Value::from_array(session.allocator(), &cow_array).map_err(|e| {
+        ProcessingError::InferenceError(format!("Failed to create input tensor: {}", e))
+    })?;
+
+    let outputs = session.run(vec![input_tensor]).map_err(|e| {
+        ProcessingError::InferenceError(format!("Recognition inference failed: {}", e))
+    })?;
+
+    let output_tensor = outputs[0].try_extract::<f32>().map_err(|e| {
+        ProcessingError::InferenceError(format!("Failed to extract embedding: {}", e))
+    })?;
+
+    let mut embedding: Vec<f32> = output_tensor.view().iter().copied().collect();
+    l2_normalize(&mut embedding);
+    Ok(embedding)
+}
+
+/// Clamp the detected box to the image bounds and crop it out, guaranteeing a
+/// non-empty region even for degenerate boxes.
+fn crop_to_bbox(image: &DynamicImage, bbox: [i32; 4]) -> DynamicImage {
+    let (img_width, img_height) = (image.width(), image.height());
+    let x1 = (bbox[0].max(0) as u32).min(img_width.saturating_sub(1));
+    let y1 = (bbox[1].max(0) as u32).min(img_height.saturating_sub(1));
+    let x2 = (bbox[2].max(0) as u32).min(img_width);
+    let y2 = (bbox[3].max(0) as u32).min(img_height);
+    let width = x2.saturating_sub(x1).max(1);
+    let height = y2.saturating_sub(y1).max(1);
+    image.crop_imm(x1, y1, width, height)
+}
+
+/// Scale a vector to unit length in place; a zero vector is left untouched.
+pub fn l2_normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for x in vector.iter_mut() {
+            *x /= norm;
+        }
+    }
+}
+
+/// Cosine similarity of two embeddings. Because [`embed_face`] returns unit
+/// vectors this is simply their dot product.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
@@ -0,0 +1,60 @@
+use crate::config::DetectionConfig;
+use crate::types::ValidationResponse;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant};
+
+/// Content-addressed cache of validation results. Because detection is
+/// deterministic for a fixed model and thresholds, identical uploads can skip
+/// inference entirely. Runs parallel to
+/// [`SESSION_CACHE`](crate::cache::SESSION_CACHE).
+lazy_static! {
+    pub(crate) static ref RESULT_CACHE: DashMap<String, (ValidationResponse, Instant)> =
+        DashMap::new();
+}
+
+const RESULT_CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// Digest the image bytes together with the model path and active thresholds so
+/// a config change naturally invalidates stale entries.
+pub fn cache_key(bytes: &[u8], model_path: &str, detection: &DetectionConfig) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher.update(model_path.as_bytes());
+    hasher.update(detection.conf_threshold.to_le_bytes());
+    hasher.update(detection.iou_threshold.to_le_bytes());
+    hasher.update(detection.input_width.to_le_bytes());
+    hasher.update(detection.input_height.to_le_bytes());
+    hasher.update(detection.cluster_size.to_le_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+pub fn get(key: &str) -> Option<ValidationResponse> {
+    let expired = match RESULT_CACHE.get(key) {
+        Some(entry) => {
+            let (response, inserted) = entry.value();
+            if inserted.elapsed() < RESULT_CACHE_TTL {
+                return Some(response.clone());
+            }
+            true
+        }
+        None => false,
+    };
+    if expired {
+        RESULT_CACHE.remove(key);
+    }
+    None
+}
+
+pub fn insert(key: String, response: &ValidationResponse) {
+    RESULT_CACHE.insert(key, (response.clone(), Instant::now()));
+}
+
+pub fn cleanup_expired_results() {
+    RESULT_CACHE.retain(|_, (_, inserted)| inserted.elapsed() < RESULT_CACHE_TTL);
+}
+
+pub fn cleanup_result_cache() {
+    RESULT_CACHE.clear();
+}
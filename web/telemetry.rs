@@ -0,0 +1,64 @@
+use crate::config::TelemetryConfig;
+use tracing_subscriber::{
+    layer::SubscriberExt, registry::LookupSpan, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
+
+/// Initialise the `tracing` pipeline: a stderr formatting layer plus, when
+/// enabled, an OTLP exporter shipping spans to a collector. The log level is
+/// read from `RUST_LOG`, defaulting to `info`.
+pub fn init(config: &TelemetryConfig) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let fmt_layer = tracing_subscriber::fmt::layer().with_target(false);
+
+    // `Option<Layer>` is itself a `Layer`, so the exporter can be switched on or
+    // off without changing the subscriber's type.
+    let otlp_layer = if config.otlp_enabled() {
+        match build_otlp_layer(config) {
+            Ok(layer) => Some(layer),
+            Err(e) => {
+                eprintln!("Failed to initialise OTLP exporter: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    Registry::default()
+        .with(filter)
+        .with(fmt_layer)
+        .with(otlp_layer)
+        .init();
+}
+
+/// Flush and tear down the OTLP exporter so buffered spans are delivered before
+/// the process exits. A no-op when the exporter was never installed.
+pub fn shutdown() {
+    opentelemetry::global::shutdown_tracer_provider();
+}
+
+fn build_otlp_layer<S>(config: &TelemetryConfig) -> Result<impl Layer<S>, Box<dyn std::error::Error>>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    use opentelemetry_otlp::WithExportConfig;
+
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(config.otlp_endpoint.clone());
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(
+            opentelemetry_sdk::trace::config().with_resource(opentelemetry_sdk::Resource::new(
+                vec![opentelemetry::KeyValue::new(
+                    "service.name",
+                    config.service_name.clone(),
+                )],
+            )),
+        )
+        .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
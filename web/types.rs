@@ -1,37 +1,47 @@
 use crate::cache::CachedSession;
+use crate::config::Config;
 use lazy_static::lazy_static;
 use ort::Environment;
 use parking_lot::RwLock;
 use serde::Serialize;
 use std::{fmt, sync::Arc};
 use thiserror::Error;
+use tokio::sync::Semaphore;
 
 lazy_static! {
     pub(crate) static ref CACHED_SESSION: RwLock<Option<CachedSession>> = RwLock::new(None);
 }
 
-// Constants
-pub const INPUT_SIZE: (u32, u32) = (640, 640);
-pub const CONF_THRESHOLD: f32 = 0.6;
-pub const IOU_THRESHOLD: f32 = 0.45;
-pub const DEFAULT_CLUSTER_SIZE: f64 = 50.0;
-
 #[derive(Clone)]
 pub struct Detection {
     pub bbox: [i32; 4],
     pub confidence: f32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationResponse {
     pub is_valid: bool,
     pub face_count: usize,
     pub message: String,
+    /// L2-normalized recognition embedding, present only when the client
+    /// requested it and exactly one face was detected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareResponse {
+    pub similarity: f32,
+    pub is_match: bool,
+    pub threshold: f32,
 }
 
 pub struct AppState {
     pub environment: Arc<Environment>,
-    pub model_path: String,
+    pub config: Arc<Config>,
+    /// Gates how many images are decoded and run through inference concurrently
+    /// across all requests (single-image, batch and compare).
+    pub semaphore: Arc<Semaphore>,
 }
 
 #[derive(Debug)]
@@ -51,7 +61,7 @@ impl From<Box<dyn std::error::Error>> for OrtErrorWrapper {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ErrorCode {
     InvalidContentType,
@@ -59,11 +69,13 @@ pub enum ErrorCode {
     NoFileProvided,
     InvalidImageFormat,
     UnsupportedFileType,
+    ImageTooLarge,
     ProcessingError,
     InternalError,
+    Unauthorized,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ErrorResponse {
     pub code: ErrorCode,
     pub message: String,
@@ -85,7 +97,7 @@ impl fmt::Display for ErrorResponse {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum ApiResponse {
     Success(ValidationResponse),